@@ -3,34 +3,177 @@ use bendy::{
     encoding::{AsString, Error as EncodeError, SingleItemEncoder, ToBencode},
 };
 use hex;
+use rand::seq::SliceRandom;
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors produced while parsing or serializing a torrent. Unlike
+/// `bendy`'s own `DecodeError`/`EncodeError`, this is the crate's public
+/// error type, so malformed input never panics or aborts the process.
+///
+/// Field-level detail (which field was missing, which integer failed to
+/// parse) lives in `Decode`'s `Display`/source chain rather than as its own
+/// variant here: `FromBencode::decode_bencode_object` is bound by `bendy` to
+/// return `DecodeError`, so a dedicated top-level variant could never
+/// actually be constructed by a caller matching on this enum.
+#[derive(Debug, Error)]
+pub enum TorrentError {
+    #[error("failed to decode bencode: {0}")]
+    Decode(#[from] DecodeError),
+    #[error("failed to encode bencode: {0}")]
+    Encode(#[from] EncodeError),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Parses a bencoded integer's string form, reporting which field it came
+/// from if it isn't a valid `u64`.
+fn parse_integer(field: &'static str, value: &str) -> Result<u64, DecodeError> {
+    value
+        .parse::<u64>()
+        .map_err(|source| decode_error(format!("invalid integer for field `{field}`: {source}")))
+}
+
+/// Wraps a plain message in an `io::Error` so it can be passed to
+/// `DecodeError::malformed_content`, which requires its argument to
+/// implement `std::error::Error` — a bare `&str`/`String` does not.
+pub(crate) fn decode_error(msg: impl Into<String>) -> DecodeError {
+    DecodeError::malformed_content(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        msg.into(),
+    ))
+}
 
 #[derive(Debug, PartialEq)]
 pub struct Bencode {
     announce: String,
+    announce_list: Vec<Vec<String>>,
     info: Info,
+    piece_layers: HashMap<[u8; 32], Vec<u8>>,
 }
 
-// TODO: create custom errors
 impl Bencode {
-    pub fn build(input: &[u8]) -> Self {
-        Self::from_bencode(input).unwrap_or_else(|err| {
-            panic!("Error parsing bencode: {:?}", err);
-        })
+    /// Assembles a metainfo from an already-built `Info`, e.g. when
+    /// creating a new `.torrent` from files on disk. `announce-list` falls
+    /// back to the single `announce` tracker as its lone tier.
+    pub(crate) fn new(announce: String, info: Info) -> Self {
+        let announce_list = vec![vec![announce.clone()]];
+
+        Bencode {
+            announce,
+            announce_list,
+            info,
+            piece_layers: HashMap::new(),
+        }
     }
 
-    pub fn info_hash(&self) -> String {
-        let bencoded_info = self.info.to_bencode().unwrap_or_else(|err| {
-            panic!("Error encoding info: {:?}", err);
-        });
+    pub fn build(input: &[u8]) -> Result<Self, TorrentError> {
+        Ok(Self::from_bencode(input)?)
+    }
+
+    pub fn info_hash(&self) -> Result<String, TorrentError> {
+        Ok(hex::encode(self.info_hash_bytes()?))
+    }
+
+    /// Raw SHA1 digest of the bencoded `info` dict, as used in tracker
+    /// announce requests and peer handshakes.
+    pub fn info_hash_bytes(&self) -> Result<[u8; 20], TorrentError> {
+        let bencoded_info = self.info.to_bencode()?;
 
         let mut hasher = Sha1::new();
         hasher.update(&bencoded_info);
 
-        hex::encode(hasher.finalize())
+        Ok(hasher.finalize().into())
+    }
+
+    /// SHA-256 info hash used to join v2/hybrid swarms (BEP 52). `None` for
+    /// v1-only torrents, which don't carry v2 metadata.
+    pub fn info_hash_v2(&self) -> Result<Option<[u8; 32]>, TorrentError> {
+        match &self.info {
+            Info::V1(_) => Ok(None),
+            Info::V2(_) | Info::Hybrid(_, _) => {
+                let bencoded_info = self.info.to_bencode()?;
+
+                let mut hasher = Sha256::new();
+                hasher.update(&bencoded_info);
+
+                Ok(Some(hasher.finalize().into()))
+            }
+        }
+    }
+
+    /// The v2 info hash truncated to 20 bytes, for contexts that compare
+    /// against v1-sized hashes (e.g. matching peers across a hybrid swarm).
+    pub fn info_hash_v2_truncated(&self) -> Result<Option<[u8; 20]>, TorrentError> {
+        let Some(hash) = self.info_hash_v2()? else {
+            return Ok(None);
+        };
+
+        let mut truncated = [0u8; 20];
+        truncated.copy_from_slice(&hash[..20]);
+
+        Ok(Some(truncated))
+    }
+
+    pub(crate) fn announce(&self) -> &str {
+        &self.announce
+    }
+
+    pub(crate) fn info(&self) -> &Info {
+        &self.info
+    }
+
+    /// Trackers grouped by tier (BEP 12): a client should try every tracker
+    /// in a tier before falling back to the next one. Each tier is
+    /// shuffled, per the spec, so repeated calls may reorder trackers
+    /// within a tier.
+    pub fn trackers(&self) -> std::vec::IntoIter<String> {
+        let mut rng = rand::thread_rng();
+
+        self.announce_list
+            .iter()
+            .flat_map(|tier| {
+                let mut tier = tier.clone();
+                tier.shuffle(&mut rng);
+                tier
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Produces a magnet URI for sharing this torrent without its full
+    /// metadata, e.g. `magnet:?xt=urn:btih:<hash>&dn=<name>&tr=<tracker>`.
+    pub fn magnet(&self) -> Result<String, TorrentError> {
+        Ok(format!(
+            "magnet:?xt=urn:btih:{hash}&dn={name}&tr={tracker}",
+            hash = self.info_hash()?,
+            name = percent_encode(self.info.name().as_bytes()),
+            tracker = percent_encode(self.announce.as_bytes()),
+        ))
     }
 }
 
+/// Percent-encodes bytes for use in a URI query string, e.g. a magnet link
+/// or a tracker announce request. Unreserved characters (`A-Za-z0-9-_.~`)
+/// are passed through verbatim, everything else is escaped as `%XX`.
+pub(crate) fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len() * 3);
+
+    for &byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char);
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
 #[derive(Debug, PartialEq)]
 struct File {
     length: u64,
@@ -44,20 +187,156 @@ enum Files {
 }
 
 #[derive(Debug, PartialEq)]
-struct Info {
+pub(crate) struct InfoV1 {
     name: String,
     piece_length: u64,
     files: Files,
     pieces: Vec<u8>,
 }
 
+impl InfoV1 {
+    fn total_length(&self) -> u64 {
+        match &self.files {
+            Files::Single(length) => *length,
+            Files::Multiple(files) => files.iter().map(|file| file.length).sum(),
+        }
+    }
+
+    fn file_layout(&self) -> Vec<(PathBuf, u64)> {
+        match &self.files {
+            Files::Single(length) => vec![(PathBuf::from(&self.name), *length)],
+            Files::Multiple(files) => files
+                .iter()
+                .map(|file| {
+                    let mut path = PathBuf::from(&self.name);
+                    path.extend(&file.path);
+                    (path, file.length)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A single file entry in a BEP 52 `file tree`, addressed by its path
+/// components relative to the torrent root and carrying its own merkle
+/// root instead of flat piece hashes.
+#[derive(Debug, PartialEq)]
+pub(crate) struct FileTreeNode {
+    path: Vec<String>,
+    length: u64,
+    /// `None` for a zero-length file, which has no pieces to hash and so no
+    /// `pieces root` key; every other leaf carries one.
+    pieces_root: Option<[u8; 32]>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct InfoV2 {
+    name: String,
+    piece_length: u64,
+    file_tree: Vec<FileTreeNode>,
+}
+
+/// A torrent's `info` dict, in its v1-only (BEP 3), v2-only (BEP 52), or
+/// hybrid form. A hybrid torrent carries both layouts in the same `info`
+/// dict, so v1 and v2 swarms can each be joined with their own info hash.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Info {
+    V1(InfoV1),
+    V2(InfoV2),
+    Hybrid(InfoV1, InfoV2),
+}
+
+impl Info {
+    /// Builds a v1-only `Info` from a flat file list, e.g. when creating a
+    /// new `.torrent` from files on disk. A single entry with an empty
+    /// path is treated as a single-file torrent.
+    pub(crate) fn new_v1(
+        name: String,
+        piece_length: u64,
+        files: Vec<(Vec<String>, u64)>,
+        pieces: Vec<u8>,
+    ) -> Self {
+        let files = if files.len() == 1 && files[0].0.is_empty() {
+            Files::Single(files[0].1)
+        } else {
+            Files::Multiple(
+                files
+                    .into_iter()
+                    .map(|(path, length)| File { length, path })
+                    .collect(),
+            )
+        };
+
+        Info::V1(InfoV1 {
+            name,
+            piece_length,
+            files,
+            pieces,
+        })
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Info::V1(info) => &info.name,
+            Info::V2(info) => &info.name,
+            Info::Hybrid(info, _) => &info.name,
+        }
+    }
+
+    pub(crate) fn piece_length(&self) -> u64 {
+        match self {
+            Info::V1(info) => info.piece_length,
+            Info::V2(info) => info.piece_length,
+            Info::Hybrid(info, _) => info.piece_length,
+        }
+    }
+
+    /// Total size in bytes of all files described by this torrent, i.e. the
+    /// `left` value to report to a tracker before anything has downloaded.
+    pub(crate) fn total_length(&self) -> u64 {
+        match self {
+            Info::V1(info) | Info::Hybrid(info, _) => info.total_length(),
+            Info::V2(info) => info.file_tree.iter().map(|node| node.length).sum(),
+        }
+    }
+
+    /// Flat SHA1 piece hashes from the v1 layout, 20 bytes each. Empty for
+    /// v2-only torrents, which verify pieces via their file tree's merkle
+    /// roots and `piece_layers` instead.
+    pub(crate) fn pieces(&self) -> &[u8] {
+        match self {
+            Info::V1(info) | Info::Hybrid(info, _) => &info.pieces,
+            Info::V2(_) => &[],
+        }
+    }
+
+    /// Ordered list of `(relative path, length)` for every file in this
+    /// torrent, rooted at `name` the way they're laid out on disk.
+    pub(crate) fn file_layout(&self) -> Vec<(PathBuf, u64)> {
+        match self {
+            Info::V1(info) | Info::Hybrid(info, _) => info.file_layout(),
+            Info::V2(info) => info
+                .file_tree
+                .iter()
+                .map(|node| {
+                    let mut path = PathBuf::from(&info.name);
+                    path.extend(&node.path);
+                    (path, node.length)
+                })
+                .collect(),
+        }
+    }
+}
+
 impl FromBencode for Bencode {
     fn decode_bencode_object(object: Object) -> Result<Self, DecodeError>
     where
         Self: Sized,
     {
         let mut announce = None;
+        let mut announce_list = None;
         let mut info = None;
+        let mut piece_layers = HashMap::new();
 
         let mut dict_dec = object.try_into_dictionary()?;
         while let Some(pair) = dict_dec.next_pair()? {
@@ -65,22 +344,104 @@ impl FromBencode for Bencode {
                 (b"announce", value) => {
                     announce = String::decode_bencode_object(value).map(Some)?;
                 }
+                (b"announce-list", value) => {
+                    let mut tier_list = value.try_into_list()?;
+                    let mut tiers = Vec::new();
+                    while let Some(tier_object) = tier_list.next_object()? {
+                        let mut tier = Vec::new();
+                        let mut urls = tier_object.try_into_list()?;
+                        while let Some(url) = urls.next_object()? {
+                            tier.push(String::decode_bencode_object(url)?);
+                        }
+                        tiers.push(tier);
+                    }
+                    announce_list = Some(tiers);
+                }
                 (b"info", value) => {
                     info = Info::decode_bencode_object(value).map(Some)?;
                 }
+                (b"piece layers", value) => {
+                    piece_layers = decode_piece_layers(value)?;
+                }
                 (_, _) => {}
             }
         }
 
         let announce = announce.ok_or_else(|| DecodeError::missing_field("announce"))?;
         let info = info.ok_or_else(|| DecodeError::missing_field("info"))?;
+        // BEP 12 trackers that don't publish an `announce-list` still have
+        // the single `announce` tracker as their only, lone tier.
+        let announce_list = announce_list.unwrap_or_else(|| vec![vec![announce.clone()]]);
 
-        Ok(Bencode { announce, info })
+        Ok(Bencode {
+            announce,
+            announce_list,
+            info,
+            piece_layers,
+        })
     }
 }
 
+impl ToBencode for Bencode {
+    const MAX_DEPTH: usize = 35;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodeError> {
+        encoder.emit_dict(|mut e| {
+            e.emit_pair(b"announce", &self.announce)?;
+            e.emit_pair(b"announce-list", &self.announce_list)?;
+            e.emit_pair(b"info", &self.info)?;
+
+            if !self.piece_layers.is_empty() {
+                e.emit_pair(b"piece layers", PieceLayers(&self.piece_layers))?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// The top-level `piece layers` dict: each key is a file's v2 merkle root
+/// and each value the concatenated SHA-256 hashes of that file's base
+/// layer.
+struct PieceLayers<'a>(&'a HashMap<[u8; 32], Vec<u8>>);
+
+impl<'a> ToBencode for PieceLayers<'a> {
+    const MAX_DEPTH: usize = 2;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodeError> {
+        encoder.emit_dict(|mut e| {
+            let mut entries: Vec<(&[u8; 32], &Vec<u8>)> = self.0.iter().collect();
+            entries.sort_by_key(|(root, _)| **root);
+
+            for (root, hashes) in entries {
+                e.emit_pair(root.as_slice(), AsString(hashes))?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// `piece layers` maps each v2 file's merkle root to the concatenated
+/// SHA-256 hashes of its base layer (the leaves of its merkle tree).
+fn decode_piece_layers(object: Object) -> Result<HashMap<[u8; 32], Vec<u8>>, DecodeError> {
+    let mut layers = HashMap::new();
+
+    let mut dict_dec = object.try_into_dictionary()?;
+    while let Some((key, value)) = dict_dec.next_pair()? {
+        let root: [u8; 32] = key
+            .try_into()
+            .map_err(|_| decode_error("piece layers key is not 32 bytes"))?;
+        let hashes = AsString::decode_bencode_object(value)?.0;
+
+        layers.insert(root, hashes);
+    }
+
+    Ok(layers)
+}
+
 impl FromBencode for Info {
-    const EXPECTED_RECURSION_DEPTH: usize = 1;
+    const EXPECTED_RECURSION_DEPTH: usize = 32;
 
     fn decode_bencode_object(object: Object) -> Result<Self, DecodeError>
     where
@@ -91,26 +452,20 @@ impl FromBencode for Info {
         let mut piece_length = None;
         let mut pieces = None;
         let mut files = Vec::new();
+        let mut meta_version = None;
+        let mut file_tree = None;
 
         let mut dict_dec = object.try_into_dictionary()?;
         while let Some(pair) = dict_dec.next_pair()? {
             match pair {
                 (b"length", value) => {
-                    length = value
-                        .try_into_integer()
-                        // TODO: handle error
-                        .map(|value| value.parse::<u64>().unwrap())
-                        .map(Some)?;
+                    length = Some(parse_integer("length", value.try_into_integer()?)?);
                 }
                 (b"name", value) => {
                     name = String::decode_bencode_object(value).map(Some)?;
                 }
                 (b"piece length", value) => {
-                    piece_length = value
-                        .try_into_integer()
-                        // TODO: handle error
-                        .map(|value| value.parse::<u64>().unwrap())
-                        .map(Some)?;
+                    piece_length = Some(parse_integer("piece length", value.try_into_integer()?)?);
                 }
                 (b"pieces", value) => {
                     pieces = AsString::decode_bencode_object(value).map(|bytes| Some(bytes.0))?;
@@ -123,6 +478,12 @@ impl FromBencode for Info {
                         files.push(file);
                     }
                 }
+                (b"meta version", value) => {
+                    meta_version = Some(parse_integer("meta version", value.try_into_integer()?)?);
+                }
+                (b"file tree", value) => {
+                    file_tree = decode_file_tree(value, &[]).map(Some)?;
+                }
                 (_, _) => {}
             }
         }
@@ -130,24 +491,143 @@ impl FromBencode for Info {
         let name = name.ok_or_else(|| DecodeError::missing_field("name"))?;
         let piece_length =
             piece_length.ok_or_else(|| DecodeError::missing_field("piece_length"))?;
-        let pieces = pieces.ok_or_else(|| DecodeError::missing_field("pieces"))?;
-        let files = if files.is_empty() {
-            // TODO: handle error
-            Files::Single(length.unwrap())
-        } else {
-            Files::Multiple(files)
+
+        if meta_version == Some(2) && file_tree.is_none() {
+            return Err(DecodeError::missing_field("file tree"));
+        }
+
+        let v1 = match pieces {
+            Some(pieces) => {
+                let files = if files.is_empty() {
+                    let length = length.ok_or_else(|| DecodeError::missing_field("length"))?;
+                    Files::Single(length)
+                } else {
+                    Files::Multiple(files)
+                };
+
+                Some(InfoV1 {
+                    name: name.clone(),
+                    piece_length,
+                    files,
+                    pieces,
+                })
+            }
+            None => None,
         };
 
-        Ok(Info {
-            name,
+        let v2 = file_tree.map(|file_tree| InfoV2 {
+            name: name.clone(),
             piece_length,
-            files,
-            pieces,
-        })
+            file_tree,
+        });
+
+        match (v1, v2) {
+            (Some(v1), Some(v2)) => Ok(Info::Hybrid(v1, v2)),
+            (Some(v1), None) => Ok(Info::V1(v1)),
+            (None, Some(v2)) => Ok(Info::V2(v2)),
+            (None, None) => Err(DecodeError::missing_field("pieces or file tree")),
+        }
+    }
+}
+
+/// Recursively decodes a BEP 52 `file tree` dict. Each node is either a
+/// directory (more nested dicts) or a leaf, marked by an empty-string key
+/// whose value holds `length` and `pieces root`.
+fn decode_file_tree(object: Object, prefix: &[String]) -> Result<Vec<FileTreeNode>, DecodeError> {
+    let mut nodes = Vec::new();
+
+    let mut dict_dec = object.try_into_dictionary()?;
+    while let Some((key, value)) = dict_dec.next_pair()? {
+        if key.is_empty() {
+            let mut length = None;
+            let mut pieces_root = None;
+
+            let mut leaf_dec = value.try_into_dictionary()?;
+            while let Some((leaf_key, leaf_value)) = leaf_dec.next_pair()? {
+                match leaf_key {
+                    b"length" => {
+                        length = leaf_value
+                            .try_into_integer()?
+                            .parse::<u64>()
+                            .map_err(DecodeError::malformed_content)
+                            .map(Some)?;
+                    }
+                    b"pieces root" => {
+                        let bytes = AsString::decode_bencode_object(leaf_value)?.0;
+                        let root: [u8; 32] = bytes
+                            .try_into()
+                            .map_err(|_| decode_error("pieces root is not 32 bytes"))?;
+
+                        pieces_root = Some(root);
+                    }
+                    _ => {}
+                }
+            }
+
+            let length = length.ok_or_else(|| DecodeError::missing_field("length"))?;
+            // A zero-length file has no pieces to hash and so no `pieces
+            // root` key; every other leaf carries one.
+            if pieces_root.is_none() && length != 0 {
+                return Err(DecodeError::missing_field("pieces root"));
+            }
+
+            nodes.push(FileTreeNode {
+                path: prefix.to_vec(),
+                length,
+                pieces_root,
+            });
+        } else {
+            let component = std::str::from_utf8(key)
+                .map_err(|_| decode_error("file tree path is not UTF-8"))?
+                .to_string();
+
+            let mut child_prefix = prefix.to_vec();
+            child_prefix.push(component);
+
+            nodes.extend(decode_file_tree(value, &child_prefix)?);
+        }
     }
+
+    Ok(nodes)
 }
 
 impl ToBencode for Info {
+    const MAX_DEPTH: usize = 34;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodeError> {
+        match self {
+            Info::V1(info) => info.encode(encoder),
+            Info::V2(info) => info.encode(encoder),
+            Info::Hybrid(v1, v2) => encoder.emit_dict(|mut e| {
+                e.emit_pair(
+                    b"file tree",
+                    FileTreeDict {
+                        nodes: v2.file_tree.iter().collect(),
+                        depth: 0,
+                    },
+                )?;
+
+                match &v1.files {
+                    Files::Single(length) => {
+                        e.emit_pair(b"length", length)?;
+                    }
+                    Files::Multiple(files) => {
+                        e.emit_pair(b"files", files)?;
+                    }
+                }
+
+                e.emit_pair(b"meta version", 2)?;
+                e.emit_pair(b"name", &v1.name)?;
+                e.emit_pair(b"piece length", v1.piece_length)?;
+                e.emit_pair(b"pieces", AsString(&v1.pieces))?;
+
+                Ok(())
+            }),
+        }
+    }
+}
+
+impl ToBencode for InfoV1 {
     const MAX_DEPTH: usize = 5;
 
     fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodeError> {
@@ -170,6 +650,108 @@ impl ToBencode for Info {
     }
 }
 
+impl ToBencode for InfoV2 {
+    const MAX_DEPTH: usize = 33;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodeError> {
+        encoder.emit_dict(|mut e| {
+            e.emit_pair(
+                b"file tree",
+                FileTreeDict {
+                    nodes: self.file_tree.iter().collect(),
+                    depth: 0,
+                },
+            )?;
+            e.emit_pair(b"meta version", 2)?;
+            e.emit_pair(b"name", &self.name)?;
+            e.emit_pair(b"piece length", self.piece_length)?;
+
+            Ok(())
+        })
+    }
+}
+
+/// One level of a `file tree` dict being rebuilt from the flat
+/// `Vec<FileTreeNode>` parsed out of it, grouping nodes that share a path
+/// component at `depth` under that component's key.
+struct FileTreeDict<'a> {
+    nodes: Vec<&'a FileTreeNode>,
+    depth: usize,
+}
+
+impl<'a> ToBencode for FileTreeDict<'a> {
+    const MAX_DEPTH: usize = 32;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodeError> {
+        encoder.emit_dict(|mut e| {
+            let mut groups: BTreeMap<&str, Vec<&FileTreeNode>> = BTreeMap::new();
+            for node in &self.nodes {
+                groups
+                    .entry(node.path[self.depth].as_str())
+                    .or_default()
+                    .push(node);
+            }
+
+            for (key, members) in groups {
+                if members.len() == 1 && members[0].path.len() == self.depth + 1 {
+                    e.emit_pair(key.as_bytes(), FileTreeLeaf(members[0]))?;
+                } else {
+                    e.emit_pair(
+                        key.as_bytes(),
+                        FileTreeDict {
+                            nodes: members,
+                            depth: self.depth + 1,
+                        },
+                    )?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+struct FileTreeLeaf<'a>(&'a FileTreeNode);
+
+impl<'a> ToBencode for FileTreeLeaf<'a> {
+    const MAX_DEPTH: usize = 2;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodeError> {
+        encoder.emit_dict(|mut e| {
+            e.emit_pair(
+                b"",
+                FileTreeLeafProperties {
+                    length: self.0.length,
+                    pieces_root: self.0.pieces_root,
+                },
+            )?;
+
+            Ok(())
+        })
+    }
+}
+
+struct FileTreeLeafProperties {
+    length: u64,
+    pieces_root: Option<[u8; 32]>,
+}
+
+impl ToBencode for FileTreeLeafProperties {
+    const MAX_DEPTH: usize = 1;
+
+    fn encode(&self, encoder: SingleItemEncoder) -> Result<(), EncodeError> {
+        encoder.emit_dict(|mut e| {
+            e.emit_pair(b"length", self.length)?;
+
+            if let Some(pieces_root) = &self.pieces_root {
+                e.emit_pair(b"pieces root", AsString(&pieces_root[..]))?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
 impl FromBencode for File {
     fn decode_bencode_object(object: Object) -> Result<Self, DecodeError>
     where
@@ -182,11 +764,7 @@ impl FromBencode for File {
         while let Some(pair) = dict_dec.next_pair()? {
             match pair {
                 (b"length", value) => {
-                    length = value
-                        .try_into_integer()
-                        // TODO: handle error
-                        .map(|value| value.parse::<u64>().unwrap())
-                        .map(Some)?
+                    length = Some(parse_integer("length", value.try_into_integer()?)?);
                 }
                 (b"path", value) => {
                     path = Vec::decode_bencode_object(value).map(Some)?;
@@ -219,13 +797,19 @@ mod tests {
     use super::*;
     use std::fs;
 
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode(b"abc123-_.~"), "abc123-_.~");
+        assert_eq!(percent_encode(&[0x00, 0xff, 0x20]), "%00%FF%20");
+    }
+
     #[test]
     fn test_penguin_torrent() {
         let file_content = fs::read("torrent_files/penguin.torrent").unwrap_or_else(|err| {
             panic!("Error reading file: {:?}", err);
         });
-        let parsed_bencode = Bencode::build(&file_content);
-        let expected_info = Info {
+        let parsed_bencode = Bencode::build(&file_content).unwrap();
+        let expected_info = InfoV1 {
             name: "The.Penguin.S01.WEBDL.720p".to_string(),
             piece_length: 8388608,
             files: Files::Multiple(vec![
@@ -266,10 +850,13 @@ mod tests {
         };
 
         assert_eq!(parsed_bencode.announce, "http://bt2.t-ru.org/ann");
-        assert_eq!(parsed_bencode.info.name, expected_info.name);
-        assert_eq!(parsed_bencode.info.piece_length, expected_info.piece_length);
-        assert_eq!(parsed_bencode.info.files, expected_info.files);
-        assert_eq!(parsed_bencode.info.pieces.len(), 60340);
+        let Info::V1(info) = &parsed_bencode.info else {
+            panic!("expected a v1 torrent");
+        };
+        assert_eq!(info.name, expected_info.name);
+        assert_eq!(info.piece_length, expected_info.piece_length);
+        assert_eq!(info.files, expected_info.files);
+        assert_eq!(info.pieces.len(), 60340);
     }
 
     #[test]
@@ -278,8 +865,8 @@ mod tests {
             panic!("Error reading file: {:?}", err);
         });
 
-        let parsed_bencode = Bencode::build(&file_content);
-        let expected_info = Info {
+        let parsed_bencode = Bencode::build(&file_content).unwrap();
+        let expected_info = InfoV1 {
             name: "Inception.2010.2160p.UHD.BDRip.HDR.x265.DD+5.1-VoX.mkv".to_string(),
             piece_length: 8388608,
             files: Files::Single(40580383319),
@@ -287,11 +874,13 @@ mod tests {
         };
 
         assert_eq!(parsed_bencode.announce, "http://bt2.t-ru.org/ann");
-        assert_eq!(parsed_bencode.info.files, expected_info.files);
-        assert_eq!(parsed_bencode.info.name, expected_info.name);
-        assert_eq!(parsed_bencode.info.piece_length, expected_info.piece_length);
-        assert_eq!(parsed_bencode.info.files, expected_info.files);
-        assert_eq!(parsed_bencode.info.pieces.len(), 96760);
+        let Info::V1(info) = &parsed_bencode.info else {
+            panic!("expected a v1 torrent");
+        };
+        assert_eq!(info.files, expected_info.files);
+        assert_eq!(info.name, expected_info.name);
+        assert_eq!(info.piece_length, expected_info.piece_length);
+        assert_eq!(info.pieces.len(), 96760);
     }
 
     #[test]
@@ -300,8 +889,8 @@ mod tests {
             panic!("Error reading file: {:?}", err);
         });
 
-        let parsed_bencode = Bencode::build(&file_content);
-        let expected_info = Info {
+        let parsed_bencode = Bencode::build(&file_content).unwrap();
+        let expected_info = InfoV1 {
             name: "sample.txt".to_string(),
             piece_length: 32768,
             files: Files::Single(92063),
@@ -312,11 +901,13 @@ mod tests {
             parsed_bencode.announce,
             "http://bittorrent-test-tracker.codecrafters.io/announce"
         );
-        assert_eq!(parsed_bencode.info.files, expected_info.files);
-        assert_eq!(parsed_bencode.info.name, expected_info.name);
-        assert_eq!(parsed_bencode.info.piece_length, expected_info.piece_length);
-        assert_eq!(parsed_bencode.info.files, expected_info.files);
-        assert_eq!(parsed_bencode.info.pieces.len(), 60);
+        let Info::V1(info) = &parsed_bencode.info else {
+            panic!("expected a v1 torrent");
+        };
+        assert_eq!(info.files, expected_info.files);
+        assert_eq!(info.name, expected_info.name);
+        assert_eq!(info.piece_length, expected_info.piece_length);
+        assert_eq!(info.pieces.len(), 60);
     }
 
     #[test]
@@ -341,10 +932,204 @@ mod tests {
         ];
 
         for (torrent, expected_hash) in test_table {
-            let bencode = Bencode::build(&torrent);
-            let hash = bencode.info_hash();
+            let bencode = Bencode::build(&torrent).unwrap();
+            let hash = bencode.info_hash().unwrap();
 
             assert_eq!(hash, expected_hash);
         }
     }
+
+    fn bencode_str(s: &str) -> Vec<u8> {
+        let mut out = format!("{}:", s.len()).into_bytes();
+        out.extend_from_slice(s.as_bytes());
+        out
+    }
+
+    fn bencode_bytes(bytes: &[u8]) -> Vec<u8> {
+        let mut out = format!("{}:", bytes.len()).into_bytes();
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    fn bencode_int(n: i64) -> Vec<u8> {
+        format!("i{}e", n).into_bytes()
+    }
+
+    /// Hand-bencoded v2 `file tree` with one normal file and one zero-length
+    /// file (no `pieces root`), since there's no `.torrent` fixture for this
+    /// case on disk.
+    fn v2_info_with_empty_file() -> Vec<u8> {
+        let pieces_root = [0x11u8; 32];
+
+        let leaf_a = [
+            b"d".as_slice(),
+            &bencode_str(""),
+            b"d",
+            &bencode_str("length"),
+            &bencode_int(4),
+            &bencode_str("pieces root"),
+            &bencode_bytes(&pieces_root),
+            b"e",
+            b"e",
+        ]
+        .concat();
+
+        let leaf_empty = [
+            b"d".as_slice(),
+            &bencode_str(""),
+            b"d",
+            &bencode_str("length"),
+            &bencode_int(0),
+            b"e",
+            b"e",
+        ]
+        .concat();
+
+        [
+            b"d".as_slice(),
+            &bencode_str("file tree"),
+            b"d",
+            &bencode_str("a"),
+            &leaf_a,
+            &bencode_str("empty"),
+            &leaf_empty,
+            b"e",
+            &bencode_str("meta version"),
+            &bencode_int(2),
+            &bencode_str("name"),
+            &bencode_str("test"),
+            &bencode_str("piece length"),
+            &bencode_int(16384),
+            b"e",
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_file_tree_keeps_zero_length_files() {
+        let info = Info::from_bencode(&v2_info_with_empty_file()).unwrap();
+
+        let Info::V2(info) = &info else {
+            panic!("expected a v2 torrent");
+        };
+
+        assert_eq!(info.file_tree.len(), 2);
+
+        let a = info.file_tree.iter().find(|node| node.path == ["a"]).unwrap();
+        assert_eq!(a.length, 4);
+        assert_eq!(a.pieces_root, Some([0x11u8; 32]));
+
+        let empty = info
+            .file_tree
+            .iter()
+            .find(|node| node.path == ["empty"])
+            .unwrap();
+        assert_eq!(empty.length, 0);
+        assert_eq!(empty.pieces_root, None);
+    }
+
+    #[test]
+    fn test_file_tree_zero_length_file_round_trips() {
+        let info = Info::from_bencode(&v2_info_with_empty_file()).unwrap();
+        let encoded = info.to_bencode().unwrap();
+        let reparsed = Info::from_bencode(&encoded).unwrap();
+
+        assert_eq!(info, reparsed);
+    }
+
+    fn encode_list(items: Vec<Vec<u8>>) -> Vec<u8> {
+        let mut out = vec![b'l'];
+        for item in items {
+            out.extend(item);
+        }
+        out.push(b'e');
+        out
+    }
+
+    /// Hand-bencoded metainfo with a two-tier `announce-list` and a single
+    /// `piece layers` entry, since there's no multi-tracker `.torrent`
+    /// fixture on disk.
+    fn minimal_torrent_with_announce_list() -> Vec<u8> {
+        let piece_root = [0x22u8; 32];
+        let piece_layer_hashes = vec![0x33u8; 32];
+
+        let tier_a = encode_list(vec![bencode_str("http://a"), bencode_str("http://b")]);
+        let tier_b = encode_list(vec![bencode_str("http://c")]);
+        let announce_list = encode_list(vec![tier_a, tier_b]);
+
+        let piece_layers = [
+            b"d".as_slice(),
+            &bencode_bytes(&piece_root),
+            &bencode_bytes(&piece_layer_hashes),
+            b"e",
+        ]
+        .concat();
+
+        let info = [
+            b"d".as_slice(),
+            &bencode_str("length"),
+            &bencode_int(10),
+            &bencode_str("name"),
+            &bencode_str("x"),
+            &bencode_str("piece length"),
+            &bencode_int(16384),
+            &bencode_str("pieces"),
+            &bencode_bytes(&[0u8; 20]),
+            b"e",
+        ]
+        .concat();
+
+        [
+            b"d".as_slice(),
+            &bencode_str("announce"),
+            &bencode_str("http://a"),
+            &bencode_str("announce-list"),
+            &announce_list,
+            &bencode_str("info"),
+            &info,
+            &bencode_str("piece layers"),
+            &piece_layers,
+            b"e",
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_announce_list_decodes_into_tiers() {
+        let bencode = Bencode::from_bencode(&minimal_torrent_with_announce_list()).unwrap();
+
+        assert_eq!(
+            bencode.announce_list,
+            vec![
+                vec!["http://a".to_string(), "http://b".to_string()],
+                vec!["http://c".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trackers_flattens_tiers_preserving_tier_order() {
+        let bencode = Bencode::from_bencode(&minimal_torrent_with_announce_list()).unwrap();
+
+        let trackers: Vec<String> = bencode.trackers().collect();
+        assert_eq!(trackers.len(), 3);
+
+        let first_tier: std::collections::HashSet<_> = trackers[..2].iter().cloned().collect();
+        assert_eq!(
+            first_tier,
+            std::collections::HashSet::from(["http://a".to_string(), "http://b".to_string()])
+        );
+        assert_eq!(trackers[2], "http://c");
+    }
+
+    #[test]
+    fn test_announce_list_and_piece_layers_round_trip() {
+        let bencode = Bencode::from_bencode(&minimal_torrent_with_announce_list()).unwrap();
+
+        let encoded = bencode.to_bencode().unwrap();
+        let reparsed = Bencode::from_bencode(&encoded).unwrap();
+
+        assert_eq!(reparsed.announce_list, bencode.announce_list);
+        assert_eq!(reparsed.piece_layers, bencode.piece_layers);
+    }
 }
@@ -0,0 +1,189 @@
+use crate::bencode::{Bencode, Info};
+use bendy::encoding::ToBencode;
+use sha1::{Digest, Sha1};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+const MIN_PIECE_LENGTH: u64 = 16 * 1024;
+const MAX_PIECE_LENGTH: u64 = 16 * 1024 * 1024;
+const TARGET_MAX_PIECES: u64 = 2000;
+
+/// Builds a `.torrent` metainfo from a file or directory on disk.
+pub struct TorrentBuilder {
+    root: PathBuf,
+    announce: String,
+    piece_length: Option<u64>,
+}
+
+impl TorrentBuilder {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            announce: String::new(),
+            piece_length: None,
+        }
+    }
+
+    pub fn announce(mut self, announce: impl Into<String>) -> Self {
+        self.announce = announce.into();
+        self
+    }
+
+    /// Overrides the automatic piece-length selection.
+    pub fn piece_length(mut self, piece_length: u64) -> Self {
+        self.piece_length = Some(piece_length);
+        self
+    }
+
+    pub fn build(self) -> io::Result<Bencode> {
+        let name = self
+            .root
+            .file_name()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "torrent root has no file name")
+            })?
+            .to_string_lossy()
+            .into_owned();
+
+        let mut files = collect_files(&self.root)?;
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let total_length: u64 = files.iter().map(|(_, length)| *length).sum();
+        let piece_length = self
+            .piece_length
+            .unwrap_or_else(|| pick_piece_length(total_length));
+        let pieces = hash_pieces(&self.root, &files, piece_length)?;
+
+        let info = Info::new_v1(name, piece_length, files, pieces);
+        let bencode = Bencode::new(self.announce, info);
+
+        // Round-trip through the real ToBencode/FromBencode path to catch
+        // encoder bugs before they ship in a `.torrent` file.
+        let encoded = bencode
+            .to_bencode()
+            .map_err(|err| io::Error::other(format!("failed to encode torrent: {:?}", err)))?;
+        let reparsed = Bencode::build(&encoded).map_err(io::Error::other)?;
+        if reparsed.info_hash().map_err(io::Error::other)? != bencode.info_hash().map_err(io::Error::other)? {
+            return Err(io::Error::other(
+                "round-trip info hash mismatch after encoding",
+            ));
+        }
+
+        Ok(bencode)
+    }
+}
+
+/// Chooses the smallest power-of-two piece length between 16 KiB and
+/// 16 MiB that keeps the piece count at or below roughly 2000, clamping at
+/// the bounds for very small or very large torrents.
+fn pick_piece_length(total_length: u64) -> u64 {
+    let mut piece_length = MIN_PIECE_LENGTH;
+
+    while piece_length < MAX_PIECE_LENGTH && total_length / piece_length > TARGET_MAX_PIECES {
+        piece_length *= 2;
+    }
+
+    piece_length
+}
+
+/// Walks `root`, returning `(path relative to root, length)` for every
+/// file, or a single entry with an empty path if `root` is itself a file.
+fn collect_files(root: &Path) -> io::Result<Vec<(Vec<String>, u64)>> {
+    let metadata = fs::metadata(root)?;
+
+    if metadata.is_file() {
+        return Ok(vec![(Vec::new(), metadata.len())]);
+    }
+
+    let mut files = Vec::new();
+    walk(root, &mut Vec::new(), &mut files)?;
+
+    Ok(files)
+}
+
+fn walk(dir: &Path, prefix: &mut Vec<String>, files: &mut Vec<(Vec<String>, u64)>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if metadata.is_dir() {
+            prefix.push(name);
+            walk(&entry.path(), prefix, files)?;
+            prefix.pop();
+        } else {
+            let mut path = prefix.clone();
+            path.push(name);
+            files.push((path, metadata.len()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads `files` in order, relative to `root`, and SHA1-hashes the
+/// concatenated stream in `piece_length`-sized blocks (the final block may
+/// be shorter).
+fn hash_pieces(root: &Path, files: &[(Vec<String>, u64)], piece_length: u64) -> io::Result<Vec<u8>> {
+    let mut pieces = Vec::new();
+    let mut buffer = vec![0u8; piece_length as usize];
+    let mut filled = 0usize;
+
+    for (path, _) in files {
+        let full_path = if path.is_empty() {
+            root.to_path_buf()
+        } else {
+            let mut full_path = root.to_path_buf();
+            full_path.extend(path);
+            full_path
+        };
+
+        let mut file = File::open(full_path)?;
+        loop {
+            let read = file.read(&mut buffer[filled..])?;
+            if read == 0 {
+                break;
+            }
+
+            filled += read;
+            if filled == buffer.len() {
+                pieces.extend_from_slice(&hash_piece(&buffer));
+                filled = 0;
+            }
+        }
+    }
+
+    if filled > 0 {
+        pieces.extend_from_slice(&hash_piece(&buffer[..filled]));
+    }
+
+    Ok(pieces)
+}
+
+fn hash_piece(bytes: &[u8]) -> [u8; 20] {
+    let mut hasher = Sha1::new();
+    hasher.update(bytes);
+
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_piece_length_clamps_to_bounds() {
+        assert_eq!(pick_piece_length(1), MIN_PIECE_LENGTH);
+        assert_eq!(pick_piece_length(u64::MAX), MAX_PIECE_LENGTH);
+    }
+
+    #[test]
+    fn test_pick_piece_length_targets_piece_count() {
+        let total_length = 100 * 1024 * 1024;
+        let piece_length = pick_piece_length(total_length);
+
+        assert!(total_length / piece_length <= TARGET_MAX_PIECES);
+        assert!(piece_length >= MIN_PIECE_LENGTH && piece_length <= MAX_PIECE_LENGTH);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod bencode;
+pub mod create;
+pub mod magnet;
+pub mod tracker;
+pub mod verify;
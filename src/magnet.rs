@@ -0,0 +1,142 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MagnetError {
+    #[error("magnet URI is missing the `xt` (exact topic) parameter")]
+    MissingInfoHash,
+    #[error("unsupported exact topic `{0}`, expected urn:btih:<hash>")]
+    UnsupportedTopic(String),
+    #[error("info hash `{0}` is neither 40 hex chars nor 32 base32 chars")]
+    InvalidInfoHash(String),
+}
+
+/// A torrent known only by its info hash, as carried by a magnet URI. It
+/// has no `info` dict, so pieces and files must still be fetched from
+/// peers before anything can be downloaded.
+#[derive(Debug, PartialEq)]
+pub struct MagnetLink {
+    pub info_hash: [u8; 20],
+    pub name: Option<String>,
+    pub trackers: Vec<String>,
+}
+
+impl MagnetLink {
+    pub fn from_magnet(uri: &str) -> Result<Self, MagnetError> {
+        let query = uri.split_once('?').map_or("", |(_, query)| query);
+
+        let mut info_hash = None;
+        let mut name = None;
+        let mut trackers = Vec::new();
+
+        for pair in query.split('&') {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next().unwrap_or("");
+            let value = percent_decode(parts.next().unwrap_or(""));
+
+            match key {
+                "xt" => info_hash = Some(parse_exact_topic(&value)?),
+                "dn" => name = Some(value),
+                "tr" => trackers.push(value),
+                _ => {}
+            }
+        }
+
+        let info_hash = info_hash.ok_or(MagnetError::MissingInfoHash)?;
+
+        Ok(MagnetLink {
+            info_hash,
+            name,
+            trackers,
+        })
+    }
+}
+
+/// Parses an `xt` parameter's value, expecting `urn:btih:<hash>` where
+/// `<hash>` is either 40 hex chars or 32 base32 chars.
+fn parse_exact_topic(value: &str) -> Result<[u8; 20], MagnetError> {
+    let hash = value
+        .strip_prefix("urn:btih:")
+        .ok_or_else(|| MagnetError::UnsupportedTopic(value.to_string()))?;
+
+    let bytes = if hash.len() == 40 {
+        hex::decode(hash).map_err(|_| MagnetError::InvalidInfoHash(hash.to_string()))?
+    } else if hash.len() == 32 {
+        base32::decode(base32::Alphabet::RFC4648 { padding: false }, hash)
+            .ok_or_else(|| MagnetError::InvalidInfoHash(hash.to_string()))?
+    } else {
+        return Err(MagnetError::InvalidInfoHash(hash.to_string()));
+    };
+
+    bytes
+        .try_into()
+        .map_err(|_| MagnetError::InvalidInfoHash(hash.to_string()))
+}
+
+/// Decodes a `%XX`-escaped, `application/x-www-form-urlencoded` style
+/// query value.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                let byte = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+                match byte {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        decoded.push(bytes[i]);
+                        i += 1;
+                    }
+                }
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_magnet_hex_info_hash() {
+        let uri = "magnet:?xt=urn:btih:d69f91e6b2ae4c542468d1073a71d4ea13879a7f&dn=sample.txt&tr=http%3A%2F%2Ftracker.example%2Fannounce";
+        let magnet = MagnetLink::from_magnet(uri).unwrap();
+
+        assert_eq!(
+            hex::encode(magnet.info_hash),
+            "d69f91e6b2ae4c542468d1073a71d4ea13879a7f"
+        );
+        assert_eq!(magnet.name, Some("sample.txt".to_string()));
+        assert_eq!(
+            magnet.trackers,
+            vec!["http://tracker.example/announce".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_from_magnet_missing_info_hash() {
+        let uri = "magnet:?dn=sample.txt";
+
+        assert!(matches!(
+            MagnetLink::from_magnet(uri),
+            Err(MagnetError::MissingInfoHash)
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::bencode::{decode_error, percent_encode, Bencode, TorrentError};
+use bendy::decoding::{Decoder, Error as DecodeError, FromBencode, Object};
+use std::net::{Ipv4Addr, SocketAddrV4};
+use thiserror::Error;
+
+/// `MissingField` isn't its own variant: like `TorrentError`, missing-field
+/// detail is bound by `bendy` to travel inside `Decode`'s `DecodeError`
+/// rather than as a top-level variant a caller could match on.
+#[derive(Debug, Error)]
+pub enum TrackerError {
+    #[error("tracker request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("failed to decode tracker response: {0}")]
+    Decode(#[from] DecodeError),
+    #[error("tracker returned a failure reason: {0}")]
+    Failure(String),
+    #[error("failed to compute info hash: {0}")]
+    Torrent(#[from] TorrentError),
+}
+
+/// A single peer returned by a tracker, ready to dial for a handshake.
+pub type Peer = SocketAddrV4;
+
+#[derive(Debug, PartialEq)]
+pub struct TrackerResponse {
+    pub interval: i64,
+    pub peers: Vec<Peer>,
+}
+
+impl FromBencode for TrackerResponse {
+    fn decode_bencode_object(object: Object) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        let mut failure_reason = None;
+        let mut interval = None;
+        let mut peers = Vec::new();
+
+        let mut dict_dec = object.try_into_dictionary()?;
+        while let Some(pair) = dict_dec.next_pair()? {
+            match pair {
+                (b"failure reason", value) => {
+                    failure_reason = String::decode_bencode_object(value).map(Some)?;
+                }
+                (b"interval", value) => {
+                    interval = value
+                        .try_into_integer()?
+                        .parse::<i64>()
+                        .map_err(DecodeError::malformed_content)
+                        .map(Some)?;
+                }
+                (b"peers", value) => {
+                    peers = decode_peers(value)?;
+                }
+                (_, _) => {}
+            }
+        }
+
+        if let Some(reason) = failure_reason {
+            return Err(decode_error(reason));
+        }
+
+        let interval = interval.ok_or_else(|| DecodeError::missing_field("interval"))?;
+
+        Ok(TrackerResponse { interval, peers })
+    }
+}
+
+/// `peers` is either a compact byte string (6 bytes per peer: 4 byte
+/// big-endian IPv4 followed by a 2 byte big-endian port) or, in the
+/// dictionary model, a list of `{ip, port}` dicts.
+fn decode_peers(object: Object) -> Result<Vec<Peer>, DecodeError> {
+    match object {
+        Object::Bytes(bytes) => {
+            if bytes.len() % 6 != 0 {
+                return Err(decode_error(
+                    "compact peers string length is not a multiple of 6",
+                ));
+            }
+
+            Ok(bytes
+                .chunks_exact(6)
+                .map(|chunk| {
+                    let ip = Ipv4Addr::new(chunk[0], chunk[1], chunk[2], chunk[3]);
+                    let port = u16::from_be_bytes([chunk[4], chunk[5]]);
+
+                    SocketAddrV4::new(ip, port)
+                })
+                .collect())
+        }
+        Object::List(mut list) => {
+            let mut peers = Vec::new();
+            while let Some(item) = list.next_object()? {
+                let mut ip = None;
+                let mut port = None;
+
+                let mut dict_dec = item.try_into_dictionary()?;
+                while let Some(pair) = dict_dec.next_pair()? {
+                    match pair {
+                        (b"ip", value) => {
+                            ip = String::decode_bencode_object(value).map(Some)?;
+                        }
+                        (b"port", value) => {
+                            port = value
+                                .try_into_integer()?
+                                .parse::<u16>()
+                                .map_err(DecodeError::malformed_content)
+                                .map(Some)?;
+                        }
+                        (_, _) => {}
+                    }
+                }
+
+                let ip = ip.ok_or_else(|| DecodeError::missing_field("ip"))?;
+                let port = port.ok_or_else(|| DecodeError::missing_field("port"))?;
+                let ip: Ipv4Addr = ip
+                    .parse()
+                    .map_err(|_| decode_error("peer ip is not valid IPv4"))?;
+
+                peers.push(SocketAddrV4::new(ip, port));
+            }
+
+            Ok(peers)
+        }
+        _ => Err(decode_error("peers is neither bytes nor a list")),
+    }
+}
+
+/// Performs HTTP tracker announce requests for a parsed torrent.
+pub struct TrackerClient {
+    http: reqwest::blocking::Client,
+}
+
+impl TrackerClient {
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Announces to `bencode`'s tracker, reporting transfer progress, and
+    /// returns the interval to wait before the next announce plus the list
+    /// of peers currently in the swarm.
+    pub fn announce(
+        &self,
+        bencode: &Bencode,
+        peer_id: [u8; 20],
+        port: u16,
+        uploaded: u64,
+        downloaded: u64,
+    ) -> Result<TrackerResponse, TrackerError> {
+        let left = bencode.info().total_length().saturating_sub(downloaded);
+        let separator = query_separator(bencode.announce());
+
+        let url = format!(
+            "{announce}{separator}info_hash={info_hash}&peer_id={peer_id}&port={port}&uploaded={uploaded}&downloaded={downloaded}&left={left}&compact=1",
+            announce = bencode.announce(),
+            info_hash = percent_encode(&bencode.info_hash_bytes()?),
+            peer_id = percent_encode(&peer_id),
+        );
+
+        let body = self.http.get(&url).send()?.bytes()?;
+
+        // A `failure reason` is reported as `TrackerError::Failure` rather
+        // than as a decode error of the normal response fields.
+        let mut decoder = Decoder::new(&body);
+        if let Some(Object::Dict(mut dict)) = decoder.next_object()? {
+            while let Some((key, value)) = dict.next_pair()? {
+                if key == b"failure reason" {
+                    return Err(TrackerError::Failure(String::decode_bencode_object(value)?));
+                }
+            }
+        }
+
+        Ok(TrackerResponse::from_bencode(&body)?)
+    }
+}
+
+impl Default for TrackerClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Some trackers' announce URLs already carry their own query string (e.g. a
+/// passkey), so the params we add must be appended with `&` in that case
+/// rather than assuming a bare `?`.
+fn query_separator(announce: &str) -> char {
+    if announce.contains('?') {
+        '&'
+    } else {
+        '?'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_separator() {
+        assert_eq!(query_separator("http://tracker.example/announce"), '?');
+        assert_eq!(
+            query_separator("http://tracker.example/announce?passkey=abc"),
+            '&'
+        );
+    }
+
+    #[test]
+    fn test_decode_compact_peers() {
+        let bytes = vec![127, 0, 0, 1, 0x1a, 0xe1, 10, 0, 0, 1, 0x1a, 0xe2];
+        let peers = decode_peers(Object::Bytes(&bytes)).unwrap();
+
+        assert_eq!(
+            peers,
+            vec![
+                SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 6881),
+                SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, 1), 6882),
+            ]
+        );
+    }
+}